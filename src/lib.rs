@@ -1,14 +1,148 @@
 //! Allows bit-level matching against values.
 //!
-//! Please refer to the [`bitpat!`] macro for details.
+//! Please refer to the [`bitpat!`] macro for details, or to one of the
+//! related entry points: [`Pattern`] for the value a pattern compiles down
+//! to, [`bitpat_table!`] for checking a whole table of patterns for overlaps
+//! and exhaustiveness, [`bitpat_extract!`] for capturing named bitfields
+//! while matching, and [`bitpat_float!`] for matching against a float's
+//! IEEE-754 bit representation.
 //!
 //! [`bitpat!`]: macro.bitpat.html
+//! [`bitpat_table!`]: macro.bitpat_table.html
+//! [`bitpat_extract!`]: macro.bitpat_extract.html
+//! [`bitpat_float!`]: macro.bitpat_float.html
 
 #![doc(html_root_url = "https://docs.rs/bitpat/0.1.0")]
 #![warn(missing_debug_implementations)]
 #![warn(missing_docs)]
 
-/// Builds a closure for bit-level matching of a value.
+use std::fmt;
+use std::ops::{BitAnd, BitOr, BitXor, Not, Sub};
+
+/// A bit pattern built by the [`bitpat!`] macro.
+///
+/// A `Pattern` is the `(relevant_mask, ones)` pair that `bitpat!` used to bake
+/// directly into a closure. Keeping it around as a value lets patterns be
+/// stored, compared, and combined with the set operators below, which are
+/// modeled on the ones `bitflags` provides for flag sets:
+///
+/// - `a & b` is the intersection: the pattern matching every value matched by
+///   both `a` and `b`. This fails (returns `None`) if `a` and `b` fix a
+///   shared bit to different values, since no pattern could then represent
+///   their overlap.
+/// - `a | b` loosens `a` and `b` down to the bits they agree on, i.e. the
+///   smallest pattern matching every value either one matches.
+/// - `a - b` removes the bits `b` fixes from the set of bits `a` fixes,
+///   leaving them as don't-care.
+///
+/// Call [`matches`][Pattern::matches] to test a value directly, or
+/// [`matcher`][Pattern::matcher] to get back a closure like the ones earlier
+/// versions of this crate produced directly from `bitpat!`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Pattern<T> {
+    relevant: T,
+    ones: T,
+}
+
+impl<T> Pattern<T> {
+    /// Creates a `Pattern` from a raw `(relevant_mask, ones)` pair.
+    ///
+    /// This is what [`bitpat!`] expands to; most users won't call it
+    /// directly.
+    pub fn new(relevant: T, ones: T) -> Self {
+        Pattern { relevant, ones }
+    }
+}
+
+impl<T> Pattern<T>
+where
+    T: Copy + PartialEq + BitAnd<Output = T>,
+{
+    /// Tests whether `value` matches this pattern.
+    pub fn matches(&self, value: T) -> bool {
+        value & self.relevant == self.ones
+    }
+
+    /// Returns a closure equivalent to what `bitpat!` used to expand to
+    /// directly, for backwards compatibility.
+    pub fn matcher(&self) -> impl Fn(T) -> bool {
+        let relevant = self.relevant;
+        let ones = self.ones;
+        move |value| value & relevant == ones
+    }
+}
+
+impl<T> Pattern<T>
+where
+    T: Copy + PartialEq + BitAnd<Output = T>,
+{
+    /// Tests whether every value matched by `other` is also matched by
+    /// `self`, i.e. whether `self` is at least as loose as `other`.
+    pub fn contains(&self, other: &Pattern<T>) -> bool {
+        self.relevant & other.relevant == self.relevant && self.ones == other.ones & self.relevant
+    }
+}
+
+impl<T> BitAnd for Pattern<T>
+where
+    T: Copy + Default + PartialEq + BitAnd<Output = T> + BitOr<Output = T> + BitXor<Output = T>,
+{
+    /// `None` if `self` and `rhs` fix a shared bit to conflicting values.
+    type Output = Option<Pattern<T>>;
+
+    fn bitand(self, rhs: Self) -> Option<Pattern<T>> {
+        let conflict = (self.ones ^ rhs.ones) & self.relevant & rhs.relevant;
+        if conflict == T::default() {
+            Some(Pattern {
+                relevant: self.relevant | rhs.relevant,
+                ones: self.ones | rhs.ones,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> BitOr for Pattern<T>
+where
+    T: Copy + PartialEq + BitAnd<Output = T> + BitOr<Output = T> + BitXor<Output = T> + Not<Output = T>,
+{
+    type Output = Pattern<T>;
+
+    fn bitor(self, rhs: Self) -> Pattern<T> {
+        let relevant = self.relevant & rhs.relevant & !(self.ones ^ rhs.ones);
+        Pattern {
+            relevant,
+            ones: self.ones & relevant,
+        }
+    }
+}
+
+impl<T> Sub for Pattern<T>
+where
+    T: Copy + PartialEq + BitAnd<Output = T> + Not<Output = T>,
+{
+    type Output = Pattern<T>;
+
+    fn sub(self, rhs: Self) -> Pattern<T> {
+        let relevant = self.relevant & !rhs.relevant;
+        Pattern {
+            relevant,
+            ones: self.ones & relevant,
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Pattern<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pattern")
+            .field("relevant", &self.relevant)
+            .field("ones", &self.ones)
+            .finish()
+    }
+}
+
+/// Builds a [`Pattern`] for bit-level matching of a value.
 ///
 /// # Syntax
 ///
@@ -16,10 +150,20 @@
 /// correspond to a bit in the values it will match against. A `1` token matches
 /// `1` bits, a `0` token matches `0` bits, and a `_` token matches either one.
 ///
-/// `bitpat!` expands to a closure that takes a value that is then matched
-/// against the specified pattern. The closure always returns a `bool`
-/// indicating whether the value matched, but its argument can be inferred to be
-/// any integer type.
+/// A braced `{lo..=hi}` token may also appear in place of a run of plain
+/// tokens. It consumes as many bits as are needed to represent `hi`, and
+/// matches when that contiguous, right-aligned field falls within the
+/// inclusive range `lo..=hi`, mirroring the range patterns rustc accepts in
+/// `match` arms.
+///
+/// `bitpat!` expands to a [`Pattern`], which can be queried directly with
+/// [`Pattern::matches`], combined with other patterns via `&`, `|` and `-`, or
+/// turned into a closure with [`Pattern::matcher`] for backwards
+/// compatibility with code that expects `bitpat!(...)` to be callable
+/// directly. A pattern containing a `{lo..=hi}` token isn't expressible as a
+/// single `(relevant_mask, ones)` pair, so `bitpat!` falls back to expanding
+/// directly to a closure in that case, just like it did before [`Pattern`]
+/// existed.
 ///
 /// If a `bitpat!` is matched against a value that consists of more bits than
 /// were specified in the pattern, the pattern is applied to the *least
@@ -31,6 +175,13 @@
 /// note that type inference might infer a larger integer type than you expect,
 /// so what seem to be excess bits in the pattern might get matched normally.
 ///
+/// # Panics
+///
+/// This truncation doesn't apply to a `{lo..=hi}` token: if its inferred
+/// width, plus any fixed `0`/`1`/`_` tokens, adds up to more bits than the
+/// matched value has, the resulting closure panics (in every build profile)
+/// rather than silently matching against a truncated or miscomputed field.
+///
 /// # Example
 ///
 /// Basic usage:
@@ -40,111 +191,749 @@
 ///
 /// # fn main() {
 /// // `0` patterns must always be 0, while `_` patterns don't matter.
-/// assert!( bitpat!(0 0 0 0 _ _ _ _)(0b00000000u8));
-/// assert!( bitpat!(0 0 0 0 _ _ _ _)(0b00001111u8));
-/// assert!( bitpat!(0 0 0 0 _ _ _ _)(0b00001000u8));
-/// assert!( bitpat!(0 0 0 0 _ _ _ _)(0b00000001u8));
+/// assert!( bitpat!(0 0 0 0 _ _ _ _).matches(0b00000000u8));
+/// assert!( bitpat!(0 0 0 0 _ _ _ _).matches(0b00001111u8));
+/// assert!( bitpat!(0 0 0 0 _ _ _ _).matches(0b00001000u8));
+/// assert!( bitpat!(0 0 0 0 _ _ _ _).matches(0b00000001u8));
 ///
-/// assert!(!bitpat!(0 0 0 0 _ _ _ _)(0b10000000u8));
-/// assert!(!bitpat!(0 0 0 0 _ _ _ _)(0b11110000u8));
-/// assert!(!bitpat!(0 0 0 0 _ _ _ _)(0b11111111u8));
-/// assert!(!bitpat!(0 0 0 0 _ _ _ _)(0b00011111u8));
+/// assert!(!bitpat!(0 0 0 0 _ _ _ _).matches(0b10000000u8));
+/// assert!(!bitpat!(0 0 0 0 _ _ _ _).matches(0b11110000u8));
+/// assert!(!bitpat!(0 0 0 0 _ _ _ _).matches(0b11111111u8));
+/// assert!(!bitpat!(0 0 0 0 _ _ _ _).matches(0b00011111u8));
 ///
 /// // `1` patterns work analogously
-/// assert!( bitpat!(1 1 1 _ _ 0 0 0)(0b11100000u8));
-/// assert!( bitpat!(1 1 1 _ _ 0 0 0)(0b11110000u8));
-/// assert!( bitpat!(1 1 1 _ _ 0 0 0)(0b11111000u8));
-/// assert!( bitpat!(1 1 1 _ _ 0 0 0)(0b11101000u8));
-///
-/// assert!(!bitpat!(1 1 1 _ _ 0 0 0)(0b00000000u8));
-/// assert!(!bitpat!(1 1 1 _ _ 0 0 0)(0b11111111u8));
-/// assert!(!bitpat!(1 1 1 _ _ 0 0 0)(0b11111100u8));
-/// assert!(!bitpat!(1 1 1 _ _ 0 0 0)(0b00001111u8));
-/// assert!(!bitpat!(1 1 1 _ _ 0 0 0)(0b11000000u8));
+/// assert!( bitpat!(1 1 1 _ _ 0 0 0).matches(0b11100000u8));
+/// assert!( bitpat!(1 1 1 _ _ 0 0 0).matches(0b11110000u8));
+/// assert!( bitpat!(1 1 1 _ _ 0 0 0).matches(0b11111000u8));
+/// assert!( bitpat!(1 1 1 _ _ 0 0 0).matches(0b11101000u8));
+///
+/// assert!(!bitpat!(1 1 1 _ _ 0 0 0).matches(0b00000000u8));
+/// assert!(!bitpat!(1 1 1 _ _ 0 0 0).matches(0b11111111u8));
+/// assert!(!bitpat!(1 1 1 _ _ 0 0 0).matches(0b11111100u8));
+/// assert!(!bitpat!(1 1 1 _ _ 0 0 0).matches(0b00001111u8));
+/// assert!(!bitpat!(1 1 1 _ _ 0 0 0).matches(0b11000000u8));
+/// # }
+/// ```
+///
+/// For code that still wants a plain closure, use [`Pattern::matcher`]:
+///
+/// ```
+/// #[macro_use] extern crate bitpat;
+///
+/// # fn main() {
+/// let matcher = bitpat!(0 0 0 0 _ _ _ _).matcher();
+/// assert!(matcher(0b00000000u8));
+/// assert!(!matcher(0b10000000u8));
+/// # }
+/// ```
+///
+/// A `{lo..=hi}` token matches a multi-bit field by value instead of bit by
+/// bit, and makes `bitpat!` expand to a closure rather than a [`Pattern`]:
+///
+/// ```
+/// #[macro_use] extern crate bitpat;
+///
+/// # fn main() {
+/// // Bits 6..=4 must fall within 2..=5.
+/// let matches = bitpat!(1 {2..=5} _ _ _ 0);
+/// assert!(matches(0b1_010_101_0u8));
+/// assert!(matches(0b1_101_001_0u8));
+/// assert!(!matches(0b1_110_001_0u8)); // 6 is out of range
+/// assert!(!matches(0b0_010_001_0u8)); // leading bit must be 1
 /// # }
 /// ```
 #[macro_export]
 macro_rules! bitpat {
-    // no more parts left, done building the masks
-    ( @build $relevant:tt $ones:tt [] ) => {
-        |value| value & ($relevant) == ($ones)
+    ( $($part:tt)+ ) => {
+        $crate::__bitpat_build!(__bitpat_finish, $crate::__bitpat_width!(0, [$($part)+]), 0, 0, 0, [], [$($part)+])
+    };
+}
+
+/// Sums up the number of bits `bitpat!`'s token list covers: 1 per `0`/`1`/`_`
+/// token, or however many bits a `{lo..=hi}` token needs to represent `hi`.
+/// Used by [`__bitpat_build`] to turn each range token's position into a
+/// shift counted from the least-significant bit. Not part of the public API.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __bitpat_width {
+    ( $acc:expr, [] ) => { $acc };
+    ( $acc:expr, [{ $lo:literal ..= $hi:literal } $($rest:tt)*] ) => {
+        $crate::__bitpat_width!(($acc + (u128::BITS - ($hi as u128).leading_zeros())), [$($rest)*])
+    };
+    ( $acc:expr, [$next:tt $($rest:tt)*] ) => {
+        $crate::__bitpat_width!(($acc + 1), [$($rest)*])
+    };
+}
+
+/// Walks `bitpat!`'s token list from the most significant bit down, exactly
+/// like the original mask-building recursion, so that a pattern longer than
+/// the value it's matched against still truncates down to the value's width
+/// instead of panicking. A `{lo..=hi}` token contributes its inferred width
+/// of don't-care bits to `relevant`/`ones` and records its own shift (derived
+/// from `$total`, the overall bit width, and `$consumed`, how much of it has
+/// been built up so far) for [`__bitpat_range_check`]. `$finish` names the
+/// macro ([`__bitpat_finish`] or [`__bitpat_matcher`]) used to turn the
+/// result into what the caller (`bitpat!` or `bitpat_float!`) expands to. Not
+/// part of the public API.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __bitpat_build {
+    ( $finish:ident, $total:expr, $relevant:expr, $ones:expr, $consumed:expr, [$($range:tt)*], [] ) => {
+        $crate::$finish!($total, $relevant, $ones, [$($range)*])
+    };
+    ( $finish:ident, $total:expr, $relevant:expr, $ones:expr, $consumed:expr, [$($range:tt)*], [{ $lo:literal ..= $hi:literal } $($rest:tt)*] ) => {
+        $crate::__bitpat_build!(
+            $finish,
+            $total,
+            ($relevant << (u128::BITS - ($hi as u128).leading_zeros())),
+            ($ones << (u128::BITS - ($hi as u128).leading_zeros())),
+            ($consumed + (u128::BITS - ($hi as u128).leading_zeros())),
+            [($total - $consumed - (u128::BITS - ($hi as u128).leading_zeros()), (u128::BITS - ($hi as u128).leading_zeros()), $lo, $hi) $($range)*],
+            [$($rest)*]
+        )
+    };
+    ( $finish:ident, $total:expr, $relevant:expr, $ones:expr, $consumed:expr, [$($range:tt)*], [_ $($rest:tt)*] ) => {
+        $crate::__bitpat_build!($finish, $total, ($relevant << 1), ($ones << 1), ($consumed + 1), [$($range)*], [$($rest)*])
+    };
+    ( $finish:ident, $total:expr, $relevant:expr, $ones:expr, $consumed:expr, [$($range:tt)*], [0 $($rest:tt)*] ) => {
+        $crate::__bitpat_build!($finish, $total, ($relevant << 1 | 1), ($ones << 1), ($consumed + 1), [$($range)*], [$($rest)*])
+    };
+    ( $finish:ident, $total:expr, $relevant:expr, $ones:expr, $consumed:expr, [$($range:tt)*], [1 $($rest:tt)*] ) => {
+        $crate::__bitpat_build!($finish, $total, ($relevant << 1 | 1), ($ones << 1 | 1), ($consumed + 1), [$($range)*], [$($rest)*])
+    };
+}
+
+/// Expands to a [`Pattern`] when `bitpat!`'s token list had no range, or to a
+/// closure performing the extra `{lo..=hi}` checks otherwise. `$total` is the
+/// pattern's overall bit width; a range wide enough to need more bits than
+/// the matched value has would otherwise make `$relevant`/`$ones` overflow
+/// their shifts, so `$relevant`/`$ones` are built *inside* the closure, after
+/// an `assert!` against the value's actual width, rather than eagerly when
+/// the pattern is constructed. This is a real `assert!`, not a
+/// `debug_assert!`, since the alternative is silently corrupting the match
+/// result in release builds rather than failing at all. Not part of the
+/// public API.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __bitpat_finish {
+    ( $total:expr, $relevant:expr, $ones:expr, [] ) => {
+        $crate::Pattern::new($relevant, $ones)
+    };
+    ( $total:expr, $relevant:expr, $ones:expr, [$($range:tt)+] ) => {
+        move |value| {
+            assert!(
+                $total as usize <= ::std::mem::size_of_val(&value) * 8,
+                "bitpat! pattern needs {} bits, which is more than the matched value has",
+                $total,
+            );
+            let __relevant = $relevant;
+            let __ones = $ones;
+            value & __relevant == __ones
+            $( && $crate::__bitpat_range_check!(value, $range) )+
+        }
+    };
+}
+
+/// Like [`__bitpat_finish`], but always expands to a closure (calling
+/// [`Pattern::matcher`] in the no-range case) rather than a bare [`Pattern`].
+/// Used by [`bitpat_float!`], which matches bits extracted from a float
+/// rather than a value that can be wrapped in a `Pattern<T>` directly. Not
+/// part of the public API.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __bitpat_matcher {
+    ( $total:expr, $relevant:expr, $ones:expr, [] ) => {
+        $crate::Pattern::new($relevant, $ones).matcher()
+    };
+    ( $total:expr, $relevant:expr, $ones:expr, [$($range:tt)+] ) => {
+        move |value| {
+            assert!(
+                $total as usize <= ::std::mem::size_of_val(&value) * 8,
+                "bitpat! pattern needs {} bits, which is more than the matched value has",
+                $total,
+            );
+            let __relevant = $relevant;
+            let __ones = $ones;
+            value & __relevant == __ones
+            $( && $crate::__bitpat_range_check!(value, $range) )+
+        }
+    };
+}
+
+/// Extracts the field a single `{lo..=hi}` token covers and checks it against
+/// its bounds. Not part of the public API.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __bitpat_range_check {
+    ( $value:ident, ($shift:expr, $width:expr, $lo:literal, $hi:literal) ) => {
+        {
+            debug_assert!($lo <= $hi, "bitpat! range has lo > hi");
+            let __field = ($value & (((1 << ($width)) - 1) << ($shift))) >> ($shift);
+            ($lo..=$hi).contains(&__field)
+        }
+    };
+}
+
+/// The result of analyzing a table of patterns built with
+/// [`bitpat_table!`].
+///
+/// A table of `bitpat!`s is how instruction decoders are usually written:
+/// one pattern per opcode, tried in some order against an `N`-bit
+/// instruction word. This mirrors the "usefulness" and exhaustiveness
+/// checking `rustc` performs on `match` arms, but for bit patterns instead
+/// of integer and range patterns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableReport {
+    /// Index pairs `(i, j)`, `i < j`, of patterns that overlap: some value
+    /// matches both `patterns[i]` and `patterns[j]`, making the decode
+    /// ambiguous.
+    pub overlaps: Vec<(usize, usize)>,
+
+    /// A value not matched by any pattern in the table, if the table isn't
+    /// exhaustive over all `2^width` possible values.
+    pub counterexample: Option<u128>,
+}
+
+impl TableReport {
+    /// Whether every possible `width`-bit value is matched by at least one
+    /// pattern in the table.
+    pub fn is_exhaustive(&self) -> bool {
+        self.counterexample.is_none()
+    }
+
+    /// Whether no two patterns in the table overlap.
+    pub fn is_unambiguous(&self) -> bool {
+        self.overlaps.is_empty()
+    }
+}
+
+/// Analyzes a table of raw `(relevant_mask, ones)` patterns over a `width`
+/// bit value, checking for overlaps and exhaustiveness.
+///
+/// This is the function [`bitpat_table!`] expands to; most users will want
+/// to go through the macro instead of building the `(mask, ones)` pairs by
+/// hand.
+///
+/// `width` is also used to cap the exhaustiveness search, which walks all
+/// `2^width` values in the worst case; keep it to the small opcode widths
+/// decoders typically use.
+///
+/// # Panics
+///
+/// Panics if `width` is greater than 128, since patterns are stored as
+/// `u128` masks and can't represent a wider value. This is checked even in
+/// release builds, since the alternative is a shift overflow inside the
+/// exhaustiveness search that's either an opaque panic or, with overflow
+/// checks off, a silently wrong result.
+pub fn analyze_table(width: u32, patterns: &[(u128, u128)]) -> TableReport {
+    assert!(
+        width <= 128,
+        "bitpat_table! width must be at most 128 bits, got {}",
+        width,
+    );
+    TableReport {
+        overlaps: overlapping_pairs(patterns),
+        counterexample: find_counterexample(width, patterns),
+    }
+}
+
+/// Returns every pair of indices whose patterns overlap, i.e. that share at
+/// least one value they both match.
+///
+/// Two ternary patterns `(mask_a, val_a)` and `(mask_b, val_b)` overlap iff,
+/// on every bit position fixed by both (`mask_a & mask_b`), their values
+/// agree: `(val_a ^ val_b) & mask_a & mask_b == 0`.
+fn overlapping_pairs(patterns: &[(u128, u128)]) -> Vec<(usize, usize)> {
+    let mut overlaps = Vec::new();
+    for i in 0..patterns.len() {
+        for j in (i + 1)..patterns.len() {
+            let (mask_a, val_a) = patterns[i];
+            let (mask_b, val_b) = patterns[j];
+            if (val_a ^ val_b) & mask_a & mask_b == 0 {
+                overlaps.push((i, j));
+            }
+        }
+    }
+    overlaps
+}
+
+/// Recursively checks whether `patterns` covers every `width`-bit value,
+/// returning a concrete uncovered value if not.
+///
+/// At each step we pick the most-significant not-yet-decided bit and split
+/// the surviving patterns into those allowing it to be `0` and those
+/// allowing it to be `1` (a pattern with `_` there goes to both branches, a
+/// fixed bit goes to one), then recurse. If a branch runs out of patterns
+/// before all bits are consumed, the bits decided so far are a
+/// counterexample.
+///
+/// `width` must be at most 128; [`analyze_table`] asserts this before calling
+/// into here.
+fn find_counterexample(width: u32, patterns: &[(u128, u128)]) -> Option<u128> {
+    fn recurse(bit: i32, prefix: u128, patterns: &[(u128, u128)]) -> Option<u128> {
+        if bit < 0 {
+            return if patterns.is_empty() { Some(prefix) } else { None };
+        }
+
+        let bitmask = 1u128 << bit;
+        let allows = |mask: u128, ones: u128, is_one: bool| {
+            mask & bitmask == 0 || (ones & bitmask != 0) == is_one
+        };
+
+        let zero: Vec<_> = patterns
+            .iter()
+            .copied()
+            .filter(|&(mask, ones)| allows(mask, ones, false))
+            .collect();
+        let one: Vec<_> = patterns
+            .iter()
+            .copied()
+            .filter(|&(mask, ones)| allows(mask, ones, true))
+            .collect();
+
+        recurse(bit - 1, prefix, &zero).or_else(|| recurse(bit - 1, prefix | bitmask, &one))
+    }
+
+    recurse(width as i32 - 1, 0, patterns)
+}
+
+/// Builds a [`TableReport`] for a table of [`bitpat!`]-style patterns over a
+/// fixed bit width.
+///
+/// # Syntax
+///
+/// `bitpat_table!` takes the table's bit width, a semicolon, and a
+/// comma-separated list of bracketed patterns using the same `1`/`0`/`_`
+/// tokens as `bitpat!`:
+///
+/// ```
+/// #[macro_use] extern crate bitpat;
+///
+/// # fn main() {
+/// let report = bitpat_table!(3;
+///     [0 0 _],
+///     [0 _ 1],
+///     [1 _ _],
+/// );
+/// assert!(!report.is_unambiguous()); // `0 0 _` and `0 _ 1` overlap at `001`
+/// assert!(!report.is_exhaustive());  // `010` isn't matched by any pattern
+/// # }
+/// ```
+///
+/// See [`analyze_table`] for what the result means.
+#[macro_export]
+macro_rules! bitpat_table {
+    ( $width:expr; $( [ $($part:tt)+ ] ),+ $(,)? ) => {
+        $crate::analyze_table($width, &[
+            $( $crate::__bitpat_table_mask!($($part)+) ),+
+        ])
     };
+}
 
-    // incrementally build the masks, shifting them to the left and adding
-    // another bit, `$next`, on the right
+/// Builds a raw `(relevant_mask, ones)` pair for one row of a
+/// [`bitpat_table!`]. Not part of the public API.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __bitpat_table_mask {
+    ( @build $relevant:tt $ones:tt [] ) => {
+        ($relevant, $ones)
+    };
     ( @build $relevant:tt $ones:tt [$next:tt $($rest:tt)*] ) => {
-        bitpat!(@build ($relevant << 1 | bitpat!(@relevant $next)) ($ones << 1 | bitpat!(@is_one $next)) [$($rest)*])
+        $crate::__bitpat_table_mask!(@build
+            ($relevant << 1 | $crate::__bitpat_table_mask!(@relevant $next))
+            ($ones << 1 | $crate::__bitpat_table_mask!(@is_one $next))
+            [$($rest)*])
     };
 
-    // Whether a bit in the value is relevant for the match
-    ( @relevant _ ) => { 0 };
-    ( @relevant 0 ) => { 1 };
-    ( @relevant 1 ) => { 1 };
+    ( @relevant _ ) => { 0u128 };
+    ( @relevant 0 ) => { 1u128 };
+    ( @relevant 1 ) => { 1u128 };
 
-    // Whether the bit must be 1
-    ( @is_one _ ) => { 0 };
-    ( @is_one 0 ) => { 0 };
-    ( @is_one 1 ) => { 1 };
+    ( @is_one _ ) => { 0u128 };
+    ( @is_one 0 ) => { 0u128 };
+    ( @is_one 1 ) => { 1u128 };
+
+    ( $($part:tt)+ ) => { $crate::__bitpat_table_mask!(@build 0u128 0u128 [$($part)+]) };
+}
+
+/// Builds a closure that both matches a pattern and extracts named
+/// bitfields from it.
+///
+/// # Syntax
+///
+/// `bitpat_extract!` takes the same `1`/`0`/`_` tokens as [`bitpat!`], plus
+/// `name:width` tokens that capture `width` bits as a named field instead of
+/// testing them, e.g. `bitpat_extract!(1 1 0 a:2 d:3)` matches the fixed
+/// `110` prefix and captures the next 2 bits as `a` and the following 3 bits
+/// as `d`.
+///
+/// The macro expands to a closure returning `Option<Fields>`, where
+/// `Fields` is a tuple of the captured fields in the order they appear in
+/// the pattern: `None` if the fixed bits didn't match, `Some(fields)`
+/// otherwise. This reuses the same mask-building recursion as `bitpat!`,
+/// additionally tracking a mask and shift per field so the captured value is
+/// `(value & field_mask) >> field_shift`.
+///
+/// Open question, not yet signed off on: the original ask for this macro was
+/// a repeated-letter grammar (`a a d d d`, one letter per captured bit)
+/// rather than `name:width`. `macro_rules!` can't test two captured token
+/// trees for equality (there's no way to ask "is this token the same `a` as
+/// last time?"), only bind and repeat them, so it can't tell where one run
+/// of repeated letters ends and an adjacent one begins — a repeated-letter
+/// grammar would need a proc macro to implement. `name:width` was shipped as
+/// a stand-in so the rest of this series wasn't blocked on that decision,
+/// but it's a real change to the requested macro surface and should be
+/// confirmed with whoever filed the original request, not treated as
+/// settled by this doc comment.
+///
+/// # Panics
+///
+/// Panics if the pattern's total width (fixed bits plus captured field
+/// widths) is more than the matched value's type has bits for, rather than
+/// silently returning a field value with bits shifted out of it.
+///
+/// # Example
+///
+/// ```
+/// #[macro_use] extern crate bitpat;
+///
+/// # fn main() {
+/// let decode = bitpat_extract!(1 1 0 a:2 d:3);
+/// assert_eq!(decode(0b110_01_101u8), Some((0b01u8, 0b101u8)));
+/// assert_eq!(decode(0b101_01_101u8), None);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! bitpat_extract {
+    ( $($part:tt)+ ) => {
+        move |value| $crate::__bitpat_extract_reverse!(value, [$($part)+], [])
+    };
+}
+
+/// Reverses the pattern's token list so [`__bitpat_extract_fold`] can fold
+/// it starting from the least-significant bit. Not part of the public API.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __bitpat_extract_reverse {
+    ( $value:ident, [], [$($acc:tt)*] ) => {
+        $crate::__bitpat_extract_fold!($value, 0, 0, 0, [], [$($acc)*])
+    };
+    ( $value:ident, [$name:ident : $w:literal $($rest:tt)*], [$($acc:tt)*] ) => {
+        $crate::__bitpat_extract_reverse!($value, [$($rest)*], [($name : $w) $($acc)*])
+    };
+    ( $value:ident, [$next:tt $($rest:tt)*], [$($acc:tt)*] ) => {
+        $crate::__bitpat_extract_reverse!($value, [$($rest)*], [$next $($acc)*])
+    };
+}
+
+/// Folds the reversed token list into the final `(relevant, ones)` check
+/// plus one `let` binding per captured field. Not part of the public API.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __bitpat_extract_fold {
+    ( $value:ident, $relevant:expr, $ones:expr, $shift:expr, [$($fname:ident = $fexpr:expr),*], [] ) => {
+        {
+            assert!(
+                ($shift) as usize <= ::std::mem::size_of_val(&$value) * 8,
+                "bitpat_extract! pattern needs {} bits, which is more than the matched value has",
+                $shift,
+            );
+            if $value & ($relevant) == ($ones) {
+                $( let $fname = $fexpr; )*
+                Some(($($fname,)*))
+            } else {
+                None
+            }
+        }
+    };
+    ( $value:ident, $relevant:expr, $ones:expr, $shift:expr, [$($fname:ident = $fexpr:expr),*], [($name:ident : $w:literal) $($rest:tt)*] ) => {
+        $crate::__bitpat_extract_fold!(
+            $value,
+            $relevant,
+            $ones,
+            ($shift + $w),
+            [$name = ($value & (((1 << $w) - 1) << ($shift))) >> ($shift) $(, $fname = $fexpr)*],
+            [$($rest)*]
+        )
+    };
+    ( $value:ident, $relevant:expr, $ones:expr, $shift:expr, [$($fname:ident = $fexpr:expr),*], [_ $($rest:tt)*] ) => {
+        $crate::__bitpat_extract_fold!($value, $relevant, $ones, ($shift + 1), [$($fname = $fexpr),*], [$($rest)*])
+    };
+    ( $value:ident, $relevant:expr, $ones:expr, $shift:expr, [$($fname:ident = $fexpr:expr),*], [0 $($rest:tt)*] ) => {
+        $crate::__bitpat_extract_fold!($value, ($relevant | (1 << $shift)), $ones, ($shift + 1), [$($fname = $fexpr),*], [$($rest)*])
+    };
+    ( $value:ident, $relevant:expr, $ones:expr, $shift:expr, [$($fname:ident = $fexpr:expr),*], [1 $($rest:tt)*] ) => {
+        $crate::__bitpat_extract_fold!($value, ($relevant | (1 << $shift)), ($ones | (1 << $shift)), ($shift + 1), [$($fname = $fexpr),*], [$($rest)*])
+    };
+}
 
-    // Entry point
-    ( $($part:tt)+ ) => {bitpat!(@build 0 0 [$($part)+])};
+/// Builds a matcher for the IEEE-754 bit representation of a float.
+///
+/// # Syntax
+///
+/// `bitpat_float!(f32; <pattern>)` or `bitpat_float!(f64; <pattern>)` takes
+/// the same `1`/`0`/`_`/`{lo..=hi}` tokens as [`bitpat!`], matched against the
+/// bits [`f32::to_bits`]/[`f64::to_bits`] returns rather than against the
+/// float itself. `f32` lays those bits out, most to least significant, as 1
+/// sign bit, 8 exponent bits, then 23 mantissa bits (`f64` is 1, 11 and 52);
+/// the pattern should cover the whole width so it doesn't silently ignore
+/// high bits it wasn't written to account for. Combined with `{lo..=hi}`
+/// tokens this reads naturally for IEEE-754 classification, e.g. "exponent
+/// all-ones, mantissa nonzero" for NaN.
+///
+/// The macro always expands to a closure (unlike `bitpat!`, which expands to
+/// a [`Pattern`] when possible), since what it matches against is a `f32`/
+/// `f64` rather than the unsigned integer a `Pattern` is built around.
+///
+/// # Example
+///
+/// ```
+/// #[macro_use] extern crate bitpat;
+///
+/// # fn main() {
+/// // Exponent all-ones, mantissa nonzero: NaN (including signaling NaNs).
+/// let is_nan = bitpat_float!(f32; _ 1 1 1 1 1 1 1 1 {1..=0x7fffff});
+/// assert!(is_nan(f32::NAN));
+/// assert!(is_nan(f32::from_bits(0x7f800001))); // smallest signaling NaN
+/// assert!(!is_nan(f32::INFINITY)); // mantissa is all zero
+/// assert!(!is_nan(1.0f32));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! bitpat_float {
+    ( $ty:ty; $($part:tt)+ ) => {
+        move |value: $ty| {
+            let value = <$ty>::to_bits(value);
+            debug_assert_eq!(
+                $crate::__bitpat_width!(0, [$($part)+]),
+                (::std::mem::size_of::<$ty>() * 8) as u32,
+                "bitpat_float! pattern doesn't cover all the bits of this float type",
+            );
+            ($crate::__bitpat_build!(__bitpat_matcher, $crate::__bitpat_width!(0, [$($part)+]), 0, 0, 0, [], [$($part)+]))(value)
+        }
+    };
 }
 
 #[cfg(test)]
 mod tests {
     #[test]
     fn mask() {
-        assert!(bitpat!(0 0 _ _ 1 _ _ _)(0b00111111));
-        assert!(bitpat!(0 0 _ _ 1 _ _ _)(0b00001111));
-        assert!(bitpat!(0 0 _ _ 1 _ _ _)(0b00001000));
-        assert!(bitpat!(0 0 _ _ 1 _ _ _)(0b00001110));
-        assert!(!bitpat!(0 0 _ _ 1 _ _ _)(0b01111111));
-        assert!(!bitpat!(0 0 _ _ 1 _ _ _)(0b10111111));
-        assert!(!bitpat!(0 0 _ _ 1 _ _ _)(0b00110111));
+        assert!(bitpat!(0 0 _ _ 1 _ _ _).matches(0b00111111));
+        assert!(bitpat!(0 0 _ _ 1 _ _ _).matches(0b00001111));
+        assert!(bitpat!(0 0 _ _ 1 _ _ _).matches(0b00001000));
+        assert!(bitpat!(0 0 _ _ 1 _ _ _).matches(0b00001110));
+        assert!(!bitpat!(0 0 _ _ 1 _ _ _).matches(0b01111111));
+        assert!(!bitpat!(0 0 _ _ 1 _ _ _).matches(0b10111111));
+        assert!(!bitpat!(0 0 _ _ 1 _ _ _).matches(0b00110111));
         for b in 0..=255u8 {
-            assert_eq!(bitpat!(1 _ _ _ _ _ _ _)(b), b >= 128);
-            assert_eq!(bitpat!(0 _ _ _ _ _ _ _)(b), b < 128);
+            assert_eq!(bitpat!(1 _ _ _ _ _ _ _).matches(b), b >= 128);
+            assert_eq!(bitpat!(0 _ _ _ _ _ _ _).matches(b), b < 128);
         }
         for b in 0..=255u8 {
-            assert_eq!(bitpat!(_ _ _ _ _ _ _ 1)(b), b & 1 != 0);
-            assert_eq!(bitpat!(_ _ _ _ _ _ _ 0)(b), b & 1 == 0);
+            assert_eq!(bitpat!(_ _ _ _ _ _ _ 1).matches(b), b & 1 != 0);
+            assert_eq!(bitpat!(_ _ _ _ _ _ _ 0).matches(b), b & 1 == 0);
         }
         for b in 0..=255u8 {
-            assert!(bitpat!(_ _ _ _ _ _ _ _)(b));
+            assert!(bitpat!(_ _ _ _ _ _ _ _).matches(b));
         }
         for b in 1..=255u8 {
-            assert!(!bitpat!(0 0 0 0 0 0 0 0)(b));
+            assert!(!bitpat!(0 0 0 0 0 0 0 0).matches(b));
         }
     }
 
     #[test]
     fn mask_too_short() {
-        assert!(bitpat!(_ _ _ _)(0b11110000));
-        assert!(bitpat!(_ _ _ _)(0b11111111));
-        assert!(bitpat!(_ _ _ _)(0b11110001));
-        assert!(bitpat!(_ _ _ _)(0b0000));
+        assert!(bitpat!(_ _ _ _).matches(0b11110000));
+        assert!(bitpat!(_ _ _ _).matches(0b11111111));
+        assert!(bitpat!(_ _ _ _).matches(0b11110001));
+        assert!(bitpat!(_ _ _ _).matches(0b0000));
 
-        assert!(bitpat!(0 0 0 0)(0b11110000));
-        assert!(bitpat!(0 0 0 0)(0b1110000));
-        assert!(bitpat!(0 0 0 0)(0b110000));
-        assert!(bitpat!(0 0 0 0)(0b10000));
-        assert!(bitpat!(0 0 0 0)(0b0000));
+        assert!(bitpat!(0 0 0 0).matches(0b11110000));
+        assert!(bitpat!(0 0 0 0).matches(0b1110000));
+        assert!(bitpat!(0 0 0 0).matches(0b110000));
+        assert!(bitpat!(0 0 0 0).matches(0b10000));
+        assert!(bitpat!(0 0 0 0).matches(0b0000));
 
-        assert!(bitpat!(1 1 1 1)(0b11111111));
-        assert!(bitpat!(1 1 1 1)(0b1111111));
-        assert!(bitpat!(1 1 1 1)(0b111111));
-        assert!(bitpat!(1 1 1 1)(0b11111));
-        assert!(bitpat!(1 1 1 1)(0b1111));
+        assert!(bitpat!(1 1 1 1).matches(0b11111111));
+        assert!(bitpat!(1 1 1 1).matches(0b1111111));
+        assert!(bitpat!(1 1 1 1).matches(0b111111));
+        assert!(bitpat!(1 1 1 1).matches(0b11111));
+        assert!(bitpat!(1 1 1 1).matches(0b1111));
     }
 
     #[test]
     fn mask_too_long() {
-        assert!(bitpat!(_   _ _ _ _ _ _ _ _)(0b11110000u8));
-        assert!(bitpat!(0   _ _ _ _ _ _ _ _)(0b11110000u8));
-        assert!(bitpat!(1   _ _ _ _ _ _ _ _)(0b11110000u8));
-        assert!(bitpat!(1   1 _ _ _ _ _ _ _)(0b11110000u8));
-        assert!(bitpat!(0   1 _ _ _ _ _ _ _)(0b11110000u8));
-        assert!(bitpat!(1   0 _ _ _ _ _ _ _)(0b01110000u8));
-        assert!(bitpat!(0   0 _ _ _ _ _ _ _)(0b01110000u8));
+        assert!(bitpat!(_   _ _ _ _ _ _ _ _).matches(0b11110000u8));
+        assert!(bitpat!(0   _ _ _ _ _ _ _ _).matches(0b11110000u8));
+        assert!(bitpat!(1   _ _ _ _ _ _ _ _).matches(0b11110000u8));
+        assert!(bitpat!(1   1 _ _ _ _ _ _ _).matches(0b11110000u8));
+        assert!(bitpat!(0   1 _ _ _ _ _ _ _).matches(0b11110000u8));
+        assert!(bitpat!(1   0 _ _ _ _ _ _ _).matches(0b01110000u8));
+        assert!(bitpat!(0   0 _ _ _ _ _ _ _).matches(0b01110000u8));
+    }
+
+    #[test]
+    fn set_algebra() {
+        let a = bitpat!(1 1 0 _ _ _ _ _);
+        let b = bitpat!(1 _ 0 1 _ _ _ _);
+
+        // `&` intersects two compatible patterns.
+        let both = (a & b).unwrap();
+        assert!(both.matches(0b11010000u8));
+        assert!(!both.matches(0b11000000u8));
+        assert!(!both.matches(0b10010000u8));
+
+        // `&` fails when the patterns disagree on a shared fixed bit.
+        let c = bitpat!(0 _ _ _ _ _ _ _);
+        assert!((a & c).is_none());
+
+        // `|` loosens down to what the two patterns agree on.
+        let either = a | bitpat!(1 1 1 _ _ _ _ _);
+        assert!(either.matches(0b11000000u8));
+        assert!(either.matches(0b11100000u8));
+        assert!(!either.matches(0b10100000u8));
+
+        // `contains` checks subsumption.
+        let loose = bitpat!(1 _ _ _ _ _ _ _);
+        let tight = bitpat!(1 1 0 _ _ _ _ _);
+        assert!(loose.contains(&tight));
+        assert!(!tight.contains(&loose));
+
+        // `-` removes a pattern's fixed bits from another's.
+        let diff = tight - bitpat!(_ 1 _ _ _ _ _ _);
+        assert!(diff.matches(0b10000000u8));
+        assert!(diff.matches(0b11000000u8));
+        assert!(!diff.matches(0b10100000u8));
+    }
+
+    #[test]
+    fn table_overlap_and_exhaustiveness() {
+        // `0 0 _` and `0 _ 1` both match `001`.
+        let report = bitpat_table!(3;
+            [0 0 _],
+            [0 _ 1],
+            [1 _ _],
+        );
+        assert_eq!(report.overlaps, vec![(0, 1)]);
+        assert!(!report.is_unambiguous());
+        assert!(!report.is_exhaustive());
+        assert_eq!(report.counterexample, Some(0b010));
+
+        // Unambiguous and exhaustive: splits all 2-bit values in half.
+        let report = bitpat_table!(2;
+            [0 _],
+            [1 _],
+        );
+        assert!(report.is_unambiguous());
+        assert!(report.is_exhaustive());
+
+        // `11` is left uncovered.
+        let report = bitpat_table!(2;
+            [0 _],
+            [1 0],
+        );
+        assert!(report.is_unambiguous());
+        assert!(!report.is_exhaustive());
+        assert_eq!(report.counterexample, Some(0b11));
+    }
+
+    #[test]
+    #[should_panic(expected = "bitpat_table! width must be at most 128 bits")]
+    fn table_width_over_128_panics() {
+        bitpat_table!(200; [1 _]);
+    }
+
+    #[test]
+    #[allow(clippy::unusual_byte_groupings)]
+    fn extract_fields() {
+        let decode = bitpat_extract!(1 1 0 a:2 d:3);
+        assert_eq!(decode(0b110_01_101u8), Some((0b01u8, 0b101u8)));
+        assert_eq!(decode(0b110_11_000u8), Some((0b11u8, 0b000u8)));
+        assert_eq!(decode(0b101_01_101u8), None);
+
+        // A pattern with no fixed bits always matches.
+        let decode = bitpat_extract!(a:4 d:4);
+        assert_eq!(decode(0b1010_0101u8), Some((0b1010u8, 0b0101u8)));
+
+        // A single field still comes back as a one-element tuple.
+        let decode = bitpat_extract!(1 _ _ d:5);
+        assert_eq!(decode(0b1_00_10110u8), Some((0b10110u8,)));
+        assert_eq!(decode(0b0_00_10110u8), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "bitpat_extract! pattern needs 10 bits")]
+    // The 10-bit field is deliberately too wide for the `u8` below; rustc can
+    // prove the intermediate mask computation would overflow and errors out
+    // at compile time without this `allow` (which is exactly the point: the
+    // `assert!` in `__bitpat_extract_fold!` exists for the cases that *aren't*
+    // caught that way, e.g. fields whose individual shifts all stay in range
+    // but whose total width still exceeds the value's).
+    #[allow(arithmetic_overflow)]
+    fn extract_wider_than_value_panics() {
+        let decode = bitpat_extract!(a:10);
+        let value: u8 = 0;
+        decode(value);
+    }
+
+    #[test]
+    #[allow(clippy::unusual_byte_groupings)]
+    fn range_token() {
+        // Bits 6..=4 must fall within 2..=5; bit 7 must be 1, bit 0 must be 0.
+        let matches = bitpat!(1 {2..=5} _ _ _ 0);
+        for field in 0..=7u8 {
+            for rest in 0..=0b111u8 {
+                let value = (1 << 7) | (field << 4) | (rest << 1);
+                assert_eq!(matches(value), (2..=5).contains(&field));
+            }
+        }
+        assert!(!matches(0b0_010_001_0u8));
+
+        // A range can stand alone, and ignores bits above its inferred width
+        // just like a plain `bitpat!` ignores bits beyond its own length.
+        let matches = bitpat!({1..=2});
+        assert!(!matches(0b00u8));
+        assert!(matches(0b01u8));
+        assert!(matches(0b10u8));
+        assert!(!matches(0b11u8));
+        assert!(matches(0b101u8));
+    }
+
+    #[test]
+    #[should_panic(expected = "bitpat! pattern needs 9 bits")]
+    fn range_wider_than_value_panics() {
+        // `{0..=200}` needs 8 bits, plus the leading `1` is 9 — too wide for a u8.
+        let matches = bitpat!(1 {0..=200});
+        matches(0u8);
+    }
+
+    #[test]
+    fn range_wider_than_u64() {
+        // `hi` here doesn't fit in a `u64`, so the width/shift arithmetic has
+        // to use `u128` throughout or it truncates and miscomputes the range.
+        let matches = bitpat!({1..=0xffff_ffff_ffff_ffff_ffffu128});
+        assert!(!matches(0u128));
+        assert!(matches(1u128));
+        assert!(matches(0xffff_ffff_ffff_ffff_ffffu128));
+        assert!(!matches(0x1_0000_0000_0000_0000_0000u128));
+    }
+
+    #[test]
+    fn float_bits() {
+        // Exponent all-ones, mantissa nonzero: NaN (including signaling NaNs).
+        let is_nan = bitpat_float!(f32; _ 1 1 1 1 1 1 1 1 {1..=0x7fffff});
+        assert!(is_nan(f32::NAN));
+        assert!(is_nan(f32::from_bits(0x7f800001)));
+        assert!(is_nan(-f32::NAN));
+        assert!(!is_nan(f32::INFINITY));
+        assert!(!is_nan(f32::NEG_INFINITY));
+        assert!(!is_nan(0.0));
+        assert!(!is_nan(1.0));
+
+        // Same classifier, but for f64.
+        let is_nan = bitpat_float!(f64; _ 1 1 1 1 1 1 1 1 1 1 1 {1u64..=0xf_ffff_ffff_ffffu64});
+        assert!(is_nan(f64::NAN));
+        assert!(!is_nan(f64::INFINITY));
+        assert!(!is_nan(1.0));
     }
 }